@@ -0,0 +1,51 @@
+/// A hasher that absorbs input incrementally and produces a fixed-size digest.
+pub trait Hasher: Default {
+    /// Absorbs more input into the hasher state.
+    fn update(&mut self, input: &[u8]);
+
+    /// Finishes hashing and returns a borrow of the digest buffer.
+    ///
+    /// Calling this repeatedly re-finalizes the current state and returns
+    /// the same buffer; it does not consume the hasher.
+    fn finalize(&mut self) -> &[u8];
+
+    /// Resets the hasher back to its initial state.
+    fn reset(&mut self);
+}
+
+/// A multihash digest: `S` raw output bytes produced by a [`Hasher`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Digest<const S: usize>([u8; S]);
+
+impl<const S: usize> Default for Digest<S> {
+    fn default() -> Self {
+        Self([0; S])
+    }
+}
+
+impl<const S: usize> Digest<S> {
+    /// Builds a digest by copying `S` bytes out of a finalized hasher's output.
+    pub fn from_slice(bytes: &[u8]) -> Self {
+        let mut digest = [0; S];
+        digest.copy_from_slice(bytes);
+        Self(digest)
+    }
+}
+
+impl<const S: usize> AsRef<[u8]> for Digest<S> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const S: usize> From<[u8; S]> for Digest<S> {
+    fn from(array: [u8; S]) -> Self {
+        Self(array)
+    }
+}
+
+impl<const S: usize> From<Digest<S>> for [u8; S] {
+    fn from(digest: Digest<S>) -> Self {
+        digest.0
+    }
+}
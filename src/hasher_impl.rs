@@ -1,128 +1,196 @@
-use crate::hasher::{Digest, Hasher, Size};
-use generic_array::GenericArray;
-
-macro_rules! derive_digest {
-    ($name:ident) => {
-        /// Multihash digest.
-        #[derive(Clone, Debug, Default, Eq, PartialEq)]
-        pub struct $name<S: Size>(GenericArray<u8, S>);
-
-        impl<S: Size> AsRef<[u8]> for $name<S> {
-            fn as_ref(&self) -> &[u8] {
-                &self.0
+use crate::hasher::Hasher;
+
+#[cfg(feature = "std")]
+macro_rules! derive_write {
+    ($name:ident $(<$S:ident>)?) => {
+        impl $(<const $S: usize>)? std::io::Write for $name $(<$S>)? {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
             }
-        }
 
-        impl<S: Size> From<GenericArray<u8, S>> for $name<S> {
-            fn from(array: GenericArray<u8, S>) -> Self {
-                Self(array)
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
             }
         }
+    };
+}
 
-        impl<S: Size> From<$name<S>> for GenericArray<u8, S> {
-            fn from(digest: $name<S>) -> Self {
-                digest.0
+#[cfg(not(feature = "std"))]
+macro_rules! derive_write {
+    ($name:ident $(<$S:ident>)?) => {
+        impl $(<const $S: usize>)? core2::io::Write for $name $(<$S>)? {
+            fn write(&mut self, buf: &[u8]) -> core2::io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
             }
-        }
 
-        impl<S: Size> Digest<S> for $name<S> {}
+            fn flush(&mut self) -> core2::io::Result<()> {
+                Ok(())
+            }
+        }
     };
 }
 
 #[cfg(any(feature = "blake2b", feature = "blake2s"))]
 macro_rules! derive_hasher_blake {
-    ($module:ident, $name:ident, $digest:ident) => {
-        derive_digest!($digest);
-
+    ($module:ident, $name:ident) => {
         /// Multihash hasher.
-        pub struct $name<S: Size> {
-            _marker: PhantomData<S>,
+        pub struct $name<const S: usize> {
+            params: $module::Params,
             state: $module::State,
+            digest: [u8; S],
         }
 
-        impl<S: Size> Default for $name<S> {
-            fn default() -> Self {
-                let mut params = $module::Params::new();
-                params.hash_length(S::to_usize());
+        impl<const S: usize> $name<S> {
+            fn from_params(params: $module::Params) -> Self {
                 Self {
-                    _marker: PhantomData,
                     state: params.to_state(),
+                    digest: [0; S],
+                    params,
                 }
             }
+
+            /// Configures this hasher as a keyed MAC, with an optional
+            /// salt and personalization string, so that subsystems which
+            /// would otherwise collide on identical inputs can be domain
+            /// separated.
+            pub fn with_params(key: &[u8], salt: &[u8], personal: &[u8]) -> Self {
+                let mut params = $module::Params::new();
+                params.hash_length(S).key(key).salt(salt).personal(personal);
+                Self::from_params(params)
+            }
         }
 
-        impl<S: Size> Hasher for $name<S> {
-            type Size = S;
-            type Digest = $digest<Self::Size>;
+        impl<const S: usize> Default for $name<S> {
+            fn default() -> Self {
+                let mut params = $module::Params::new();
+                params.hash_length(S);
+                Self::from_params(params)
+            }
+        }
 
+        impl<const S: usize> Hasher for $name<S> {
             fn update(&mut self, input: &[u8]) {
                 self.state.update(input);
             }
 
-            fn finalize(&self) -> Self::Digest {
-                let digest = GenericArray::clone_from_slice(self.state.finalize().as_bytes());
-                Self::Digest::from(digest)
+            fn finalize(&mut self) -> &[u8] {
+                self.digest.copy_from_slice(self.state.finalize().as_bytes());
+                &self.digest
             }
 
             fn reset(&mut self) {
-                let Self { state, .. } = Self::default();
-                self.state = state;
+                self.state = self.params.to_state();
             }
         }
+
+        derive_write!($name<S>);
     };
 }
 
 #[cfg(feature = "blake2b")]
 pub mod blake2b {
     use super::*;
-    use core::marker::PhantomData;
-    use generic_array::typenum::{U32, U64};
 
-    derive_hasher_blake!(blake2b_simd, Blake2bHasher, Blake2bDigest);
+    derive_hasher_blake!(blake2b_simd, Blake2bHasher);
 
     /// 256 bit blake2b hasher.
-    pub type Blake2b256 = Blake2bHasher<U32>;
+    pub type Blake2b256 = Blake2bHasher<32>;
 
     /// 512 bit blake2b hasher.
-    pub type Blake2b512 = Blake2bHasher<U64>;
+    pub type Blake2b512 = Blake2bHasher<64>;
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn with_params_changes_output() {
+            let mut keyed = Blake2b256::with_params(b"secret", b"", b"");
+            keyed.update(b"hello");
+            let keyed_digest = keyed.finalize().to_vec();
+
+            let mut unkeyed = Blake2b256::default();
+            unkeyed.update(b"hello");
+            let unkeyed_digest = unkeyed.finalize().to_vec();
+
+            assert_ne!(keyed_digest, unkeyed_digest);
+        }
+
+        #[test]
+        fn reset_preserves_params() {
+            let mut hasher = Blake2b256::with_params(b"secret", b"", b"");
+            hasher.update(b"garbage");
+            hasher.reset();
+            hasher.update(b"hello");
+            let reset_digest = hasher.finalize().to_vec();
+
+            let mut fresh = Blake2b256::with_params(b"secret", b"", b"");
+            fresh.update(b"hello");
+            let fresh_digest = fresh.finalize().to_vec();
+
+            assert_eq!(reset_digest, fresh_digest);
+        }
+
+        #[test]
+        fn reset_without_params_matches_default() {
+            let mut hasher = Blake2b256::default();
+            hasher.update(b"garbage");
+            hasher.reset();
+            hasher.update(b"hello");
+            let reset_digest = hasher.finalize().to_vec();
+
+            let mut fresh = Blake2b256::default();
+            fresh.update(b"hello");
+            let fresh_digest = fresh.finalize().to_vec();
+
+            assert_eq!(reset_digest, fresh_digest);
+        }
+    }
 }
 
 #[cfg(feature = "blake2s")]
 pub mod blake2s {
     use super::*;
-    use core::marker::PhantomData;
-    use generic_array::typenum::{U16, U32};
 
-    derive_hasher_blake!(blake2s_simd, Blake2sHasher, Blake2sDigest);
+    derive_hasher_blake!(blake2s_simd, Blake2sHasher);
 
     /// 256 bit blake2b hasher.
-    pub type Blake2s128 = Blake2sHasher<U16>;
+    pub type Blake2s128 = Blake2sHasher<16>;
 
     /// 512 bit blake2b hasher.
-    pub type Blake2s256 = Blake2sHasher<U32>;
+    pub type Blake2s256 = Blake2sHasher<32>;
 }
 
 #[cfg(feature = "digest")]
 macro_rules! derive_hasher_sha {
-    ($module:ty, $name:ident, $size:ty, $digest:ident) => {
+    ($module:ty, $name:ident, $size:expr) => {
         /// Multihash hasher.
-        #[derive(Default)]
         pub struct $name {
             state: $module,
+            digest: [u8; $size],
         }
 
-        impl $crate::hasher::Hasher for $name {
-            type Size = $size;
-            type Digest = $digest<Self::Size>;
+        impl Default for $name {
+            fn default() -> Self {
+                Self {
+                    state: Default::default(),
+                    digest: [0; $size],
+                }
+            }
+        }
 
+        impl $crate::hasher::Hasher for $name {
             fn update(&mut self, input: &[u8]) {
                 use digest::Digest;
                 self.state.update(input)
             }
 
-            fn finalize(&self) -> Self::Digest {
+            fn finalize(&mut self) -> &[u8] {
                 use digest::Digest;
-                Self::Digest::from(self.state.clone().finalize())
+                self.digest.copy_from_slice(&self.state.clone().finalize());
+                &self.digest
             }
 
             fn reset(&mut self) {
@@ -130,63 +198,195 @@ macro_rules! derive_hasher_sha {
                 self.state.reset();
             }
         }
+
+        derive_write!($name);
     };
 }
 
 #[cfg(feature = "sha1")]
 pub mod sha1 {
     use super::*;
-    use generic_array::typenum::U20;
 
-    derive_digest!(Sha1Digest);
-    derive_hasher_sha!(::sha1::Sha1, Sha1, U20, Sha1Digest);
+    derive_hasher_sha!(::sha1::Sha1, Sha1, 20);
 }
 
 #[cfg(feature = "sha2")]
 pub mod sha2 {
     use super::*;
-    use generic_array::typenum::{U32, U64};
 
-    derive_digest!(Sha2Digest);
-    derive_hasher_sha!(sha_2::Sha256, Sha2_256, U32, Sha2Digest);
-    derive_hasher_sha!(sha_2::Sha512, Sha2_512, U64, Sha2Digest);
+    derive_hasher_sha!(sha_2::Sha256, Sha2_256, 32);
+    derive_hasher_sha!(sha_2::Sha512, Sha2_512, 64);
+
+    #[cfg(all(test, feature = "std"))]
+    mod tests {
+        use super::*;
+        use std::io::Write;
+
+        #[test]
+        fn write_matches_update() {
+            let input = b"the quick brown fox jumps over the lazy dog";
+
+            let mut via_update = Sha2_256::default();
+            via_update.update(input);
+
+            let mut via_write = Sha2_256::default();
+            std::io::copy(&mut &input[..], &mut via_write).unwrap();
+
+            assert_eq!(via_update.finalize(), via_write.finalize());
+        }
+    }
 }
 
 #[cfg(feature = "sha3")]
 pub mod sha3 {
     use super::*;
-    use generic_array::typenum::{U28, U32, U48, U64};
-
-    derive_digest!(Sha3Digest);
-    derive_hasher_sha!(sha_3::Sha3_224, Sha3_224, U28, Sha3Digest);
-    derive_hasher_sha!(sha_3::Sha3_256, Sha3_256, U32, Sha3Digest);
-    derive_hasher_sha!(sha_3::Sha3_384, Sha3_384, U48, Sha3Digest);
-    derive_hasher_sha!(sha_3::Sha3_512, Sha3_512, U64, Sha3Digest);
-
-    derive_digest!(KeccakDigest);
-    derive_hasher_sha!(sha_3::Keccak224, Keccak224, U28, KeccakDigest);
-    derive_hasher_sha!(sha_3::Keccak256, Keccak256, U32, KeccakDigest);
-    derive_hasher_sha!(sha_3::Keccak384, Keccak384, U48, KeccakDigest);
-    derive_hasher_sha!(sha_3::Keccak512, Keccak512, U64, KeccakDigest);
+
+    derive_hasher_sha!(sha_3::Sha3_224, Sha3_224, 28);
+    derive_hasher_sha!(sha_3::Sha3_256, Sha3_256, 32);
+    derive_hasher_sha!(sha_3::Sha3_384, Sha3_384, 48);
+    derive_hasher_sha!(sha_3::Sha3_512, Sha3_512, 64);
+
+    derive_hasher_sha!(sha_3::Keccak224, Keccak224, 28);
+    derive_hasher_sha!(sha_3::Keccak256, Keccak256, 32);
+    derive_hasher_sha!(sha_3::Keccak384, Keccak384, 48);
+    derive_hasher_sha!(sha_3::Keccak512, Keccak512, 64);
 }
 
-pub mod identity {
+#[cfg(feature = "sha3")]
+pub mod shake {
     use super::*;
-    use generic_array::typenum::U32;
+    use digest::{ExtendableOutput, Update, XofReader as _};
 
-    derive_digest!(IdentityDigest);
+    /// A hasher that absorbs input and, once finalized, can squeeze an
+    /// arbitrary number of output bytes instead of a fixed-size digest.
+    pub trait ExtendableHasher {
+        /// Absorbs more input into the hasher state.
+        fn update(&mut self, input: &[u8]);
 
-    /// Identity hasher.
+        /// Consumes the hasher and returns a reader for squeezing output.
+        /// No further input can be absorbed once squeezing has started.
+        fn finalize_xof(self) -> XofReader;
+    }
+
+    /// Squeezes output bytes from a finalized [`ExtendableHasher`].
+    pub enum XofReader {
+        Shake128(sha_3::Shake128Reader),
+        Shake256(sha_3::Shake256Reader),
+    }
+
+    impl XofReader {
+        /// Fills `out` with the next `out.len()` squeezed bytes.
+        pub fn read(&mut self, out: &mut [u8]) {
+            match self {
+                Self::Shake128(reader) => reader.read(out),
+                Self::Shake256(reader) => reader.read(out),
+            }
+        }
+    }
+
+    /// SHAKE128 extendable-output hasher.
+    #[derive(Default)]
+    pub struct Shake128Hasher(sha_3::Shake128);
+
+    impl ExtendableHasher for Shake128Hasher {
+        fn update(&mut self, input: &[u8]) {
+            Update::update(&mut self.0, input);
+        }
+
+        fn finalize_xof(self) -> XofReader {
+            XofReader::Shake128(self.0.finalize_xof())
+        }
+    }
+
+    /// SHAKE256 extendable-output hasher.
     #[derive(Default)]
-    pub struct IdentityHasher<S: Size> {
-        bytes: GenericArray<u8, S>,
+    pub struct Shake256Hasher(sha_3::Shake256);
+
+    impl ExtendableHasher for Shake256Hasher {
+        fn update(&mut self, input: &[u8]) {
+            Update::update(&mut self.0, input);
+        }
+
+        fn finalize_xof(self) -> XofReader {
+            XofReader::Shake256(self.0.finalize_xof())
+        }
+    }
+
+    /// Fixed-length SHAKE256 hasher that always squeezes exactly `S` bytes,
+    /// so it plugs into the regular `Hasher` machinery. The multihash
+    /// `Code` table has no entry for SHAKE256.
+    pub struct Shake256<const S: usize> {
+        state: sha_3::Shake256,
+        digest: [u8; S],
+    }
+
+    impl<const S: usize> Default for Shake256<S> {
+        fn default() -> Self {
+            Self {
+                state: Default::default(),
+                digest: [0; S],
+            }
+        }
+    }
+
+    impl<const S: usize> Hasher for Shake256<S> {
+        fn update(&mut self, input: &[u8]) {
+            Update::update(&mut self.state, input);
+        }
+
+        fn finalize(&mut self) -> &[u8] {
+            let mut reader = self.state.clone().finalize_xof();
+            reader.read(&mut self.digest);
+            &self.digest
+        }
+
+        fn reset(&mut self) {
+            self.state = Default::default();
+        }
+    }
+
+    derive_write!(Shake256<S>);
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn to_hex(bytes: &[u8]) -> String {
+            bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+        }
+
+        #[test]
+        fn shake256_known_answer() {
+            let mut hasher = Shake256::<32>::default();
+            hasher.update(b"");
+            let digest = hasher.finalize();
+            assert_eq!(
+                to_hex(digest),
+                "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f"
+            );
+        }
+    }
+}
+
+pub mod identity {
+    use super::*;
+
+    /// Identity hasher.
+    pub struct IdentityHasher<const S: usize> {
+        bytes: [u8; S],
         i: usize,
     }
 
-    impl<S: Size> Hasher for IdentityHasher<S> {
-        type Size = S;
-        type Digest = IdentityDigest<Self::Size>;
+    impl<const S: usize> Default for IdentityHasher<S> {
+        fn default() -> Self {
+            Self {
+                bytes: [0; S],
+                i: 0,
+            }
+        }
+    }
 
+    impl<const S: usize> Hasher for IdentityHasher<S> {
         fn update(&mut self, input: &[u8]) {
             let start = self.i;
             let end = start + input.len();
@@ -194,64 +394,211 @@ pub mod identity {
             self.i = end;
         }
 
-        fn finalize(&self) -> Self::Digest {
-            Self::Digest::from(self.bytes.clone())
+        fn finalize(&mut self) -> &[u8] {
+            &self.bytes
         }
 
         fn reset(&mut self) {
-            self.bytes = Default::default();
+            self.bytes = [0; S];
             self.i = 0;
         }
     }
 
+    derive_write!(IdentityHasher<S>);
+
     /// 256 bit Identity hasher.
-    pub type Identity256 = IdentityHasher<U32>;
+    pub type Identity256 = IdentityHasher<32>;
 }
 
 pub mod unknown {
+    /// Digest for multihash codes this crate doesn't know how to hash,
+    /// e.g. ones read back from an already-encoded multihash.
+    pub type UnknownDigest<const S: usize> = crate::hasher::Digest<S>;
+}
+
+#[cfg(feature = "xxhash")]
+pub mod xxhash {
     use super::*;
-    derive_digest!(UnknownDigest);
+    use core::hash::Hasher as _;
+    use twox_hash::xxh3::HasherExt;
+    use twox_hash::{Xxh3Hash128, Xxh3Hash64};
+
+    /// 64 bit XXH3 hasher, for fast non-cryptographic content addressing.
+    pub struct Xxh3_64 {
+        seed: u64,
+        state: Xxh3Hash64,
+        digest: [u8; 8],
+    }
+
+    impl Xxh3_64 {
+        /// Creates a hasher seeded with `seed`, so callers can domain
+        /// separate otherwise-identical inputs.
+        pub fn with_seed(seed: u64) -> Self {
+            Self {
+                seed,
+                state: Xxh3Hash64::with_seed(seed),
+                digest: [0; 8],
+            }
+        }
+    }
+
+    impl Default for Xxh3_64 {
+        fn default() -> Self {
+            Self::with_seed(0)
+        }
+    }
+
+    impl Hasher for Xxh3_64 {
+        fn update(&mut self, input: &[u8]) {
+            self.state.write(input);
+        }
+
+        fn finalize(&mut self) -> &[u8] {
+            self.digest = self.state.finish().to_le_bytes();
+            &self.digest
+        }
+
+        fn reset(&mut self) {
+            self.state = Xxh3Hash64::with_seed(self.seed);
+        }
+    }
+
+    derive_write!(Xxh3_64);
+
+    /// 128 bit XXH3 hasher, for fast non-cryptographic content addressing.
+    pub struct Xxh3_128 {
+        seed: u64,
+        state: Xxh3Hash128,
+        digest: [u8; 16],
+    }
+
+    impl Xxh3_128 {
+        /// Creates a hasher seeded with `seed`, so callers can domain
+        /// separate otherwise-identical inputs.
+        pub fn with_seed(seed: u64) -> Self {
+            Self {
+                seed,
+                state: Xxh3Hash128::with_seed(seed),
+                digest: [0; 16],
+            }
+        }
+    }
+
+    impl Default for Xxh3_128 {
+        fn default() -> Self {
+            Self::with_seed(0)
+        }
+    }
+
+    impl Hasher for Xxh3_128 {
+        fn update(&mut self, input: &[u8]) {
+            self.state.write(input);
+        }
+
+        fn finalize(&mut self) -> &[u8] {
+            self.digest = self.state.finish_ext().to_le_bytes();
+            &self.digest
+        }
+
+        fn reset(&mut self) {
+            self.state = Xxh3Hash128::with_seed(self.seed);
+        }
+    }
+
+    derive_write!(Xxh3_128);
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn xxh3_64_known_answer() {
+            let mut hasher = Xxh3_64::default();
+            hasher.update(b"");
+            assert_eq!(hasher.finalize(), 0x2d06800538d394c2u64.to_le_bytes());
+        }
+
+        #[test]
+        fn xxh3_64_seed_changes_output() {
+            let mut a = Xxh3_64::with_seed(0);
+            a.update(b"hello");
+            let mut b = Xxh3_64::with_seed(1);
+            b.update(b"hello");
+            assert_ne!(a.finalize(), b.finalize());
+        }
+
+        #[test]
+        fn xxh3_64_reset_preserves_seed() {
+            let mut hasher = Xxh3_64::with_seed(42);
+            hasher.update(b"garbage");
+            hasher.reset();
+            hasher.update(b"hello");
+            let reset_digest = hasher.finalize().to_vec();
+
+            let mut fresh = Xxh3_64::with_seed(42);
+            fresh.update(b"hello");
+            assert_eq!(reset_digest, fresh.finalize());
+        }
+
+        #[test]
+        fn xxh3_128_deterministic_and_seed_sensitive() {
+            let mut a = Xxh3_128::with_seed(0);
+            a.update(b"hello");
+            let mut a_again = Xxh3_128::with_seed(0);
+            a_again.update(b"hello");
+            assert_eq!(a.finalize(), a_again.finalize());
+
+            let mut b = Xxh3_128::with_seed(1);
+            b.update(b"hello");
+            assert_ne!(a.finalize(), b.finalize());
+        }
+
+        #[test]
+        fn xxh3_128_reset_preserves_seed() {
+            let mut hasher = Xxh3_128::with_seed(42);
+            hasher.update(b"garbage");
+            hasher.reset();
+            hasher.update(b"hello");
+            let reset_digest = hasher.finalize().to_vec();
+
+            let mut fresh = Xxh3_128::with_seed(42);
+            fresh.update(b"hello");
+            assert_eq!(reset_digest, fresh.finalize());
+        }
+    }
 }
 
 #[cfg(feature = "strobe")]
 pub mod strobe {
     use super::*;
-    use core::marker::PhantomData;
-    use generic_array::typenum::{U32, U64};
     use strobe_rs::{SecParam, Strobe};
 
-    derive_digest!(StrobeDigest);
-
     /// Strobe hasher.
-    pub struct StrobeHasher<S: Size> {
-        _marker: PhantomData<S>,
+    pub struct StrobeHasher<const S: usize> {
         strobe: Strobe,
         initialized: bool,
+        digest: [u8; S],
     }
 
-    impl<S: Size> Default for StrobeHasher<S> {
+    impl<const S: usize> Default for StrobeHasher<S> {
         fn default() -> Self {
             Self {
-                _marker: PhantomData,
                 strobe: Strobe::new(b"StrobeHash", SecParam::B128),
                 initialized: false,
+                digest: [0; S],
             }
         }
     }
 
-    impl<S: Size> Hasher for StrobeHasher<S> {
-        type Size = S;
-        type Digest = StrobeDigest<Self::Size>;
-
+    impl<const S: usize> Hasher for StrobeHasher<S> {
         fn update(&mut self, input: &[u8]) {
             self.strobe.ad(input, self.initialized);
             self.initialized = true;
         }
 
-        fn finalize(&self) -> Self::Digest {
-            let mut hash = GenericArray::default();
-            self.strobe.clone().prf(&mut hash, false);
-            Self::Digest::from(hash)
+        fn finalize(&mut self) -> &[u8] {
+            self.strobe.clone().prf(&mut self.digest, false);
+            &self.digest
         }
 
         fn reset(&mut self) {
@@ -261,9 +608,11 @@ pub mod strobe {
         }
     }
 
+    derive_write!(StrobeHasher<S>);
+
     /// 256 bit strobe hasher.
-    pub type Strobe256 = StrobeHasher<U32>;
+    pub type Strobe256 = StrobeHasher<32>;
 
     /// 512 bit strobe hasher.
-    pub type Strobe512 = StrobeHasher<U64>;
+    pub type Strobe512 = StrobeHasher<64>;
 }
@@ -0,0 +1,76 @@
+/// The multicodec hashing algorithm codes supported by this crate's hashers.
+///
+/// See the [multicodec table](https://github.com/multiformats/multicodec/blob/master/table.csv)
+/// for the canonical assignment of these codes.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Code {
+    /// Identity (raw bytes).
+    Identity,
+    /// Sha1.
+    Sha1,
+    /// Sha2-256.
+    Sha2_256,
+    /// Sha2-512.
+    Sha2_512,
+    /// Sha3-224.
+    Sha3_224,
+    /// Sha3-256.
+    Sha3_256,
+    /// Sha3-384.
+    Sha3_384,
+    /// Sha3-512.
+    Sha3_512,
+    /// Keccak-224.
+    Keccak224,
+    /// Keccak-256.
+    Keccak256,
+    /// Keccak-384.
+    Keccak384,
+    /// Keccak-512.
+    Keccak512,
+    /// Blake2b-256.
+    Blake2b256,
+    /// Blake2b-512.
+    Blake2b512,
+    /// Blake2s-128.
+    Blake2s128,
+    /// Blake2s-256.
+    Blake2s256,
+    /// Strobe-256.
+    Strobe256,
+    /// Strobe-512.
+    Strobe512,
+    /// Xxh3-64.
+    Xxh3_64,
+    /// Xxh3-128.
+    Xxh3_128,
+}
+
+impl Code {
+    /// Returns the multicodec code.
+    pub fn code(&self) -> u64 {
+        match self {
+            Self::Identity => 0x00,
+            Self::Sha1 => 0x11,
+            Self::Sha2_256 => 0x12,
+            Self::Sha2_512 => 0x13,
+            Self::Sha3_512 => 0x14,
+            Self::Sha3_384 => 0x15,
+            Self::Sha3_256 => 0x16,
+            Self::Sha3_224 => 0x17,
+            Self::Keccak224 => 0x1a,
+            Self::Keccak256 => 0x1b,
+            Self::Keccak384 => 0x1c,
+            Self::Keccak512 => 0x1d,
+            Self::Blake2b256 => 0xb220,
+            Self::Blake2b512 => 0xb240,
+            Self::Blake2s128 => 0xb250,
+            Self::Blake2s256 => 0xb260,
+            Self::Strobe256 => 0x3312e9,
+            Self::Strobe512 => 0x3312ea,
+            Self::Xxh3_64 => 0xb3e3,
+            Self::Xxh3_128 => 0xb3e4,
+        }
+    }
+}